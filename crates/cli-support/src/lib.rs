@@ -36,6 +36,9 @@ pub struct Bindgen {
     // Experimental support for `WeakRefGroup`, an upcoming ECMAScript feature.
     // Currently only enable-able through an env var.
     weak_refs: bool,
+    web_async: bool,
+    check_toolchain: bool,
+    wasi: bool,
 }
 
 enum Input {
@@ -59,6 +62,9 @@ impl Bindgen {
             demangle: true,
             keep_debug: false,
             weak_refs: env::var("WASM_BINDGEN_WEAKREF").is_ok(),
+            web_async: false,
+            check_toolchain: false,
+            wasi: false,
         }
     }
 
@@ -122,6 +128,23 @@ impl Bindgen {
         self
     }
 
+    /// Emit an async `init` entry point for the `browser` target instead of
+    /// instantiating the wasm module synchronously. Has no effect unless
+    /// `browser` is also enabled; `no_modules` produces a classic script
+    /// and can't host the ES-module-only `init` this emits.
+    ///
+    /// Synchronous compilation of `WebAssembly.Module` is rejected by
+    /// browsers for modules over 4KB when run on the main thread, so large
+    /// outputs can otherwise only be loaded from a worker. When enabled the
+    /// generated shim exposes an async `init(input)` which prefers
+    /// `WebAssembly.instantiateStreaming`, falling back to instantiating an
+    /// already-fetched `ArrayBuffer` when streaming compilation isn't
+    /// available.
+    pub fn web_async(&mut self, web_async: bool) -> &mut Bindgen {
+        self.web_async = web_async;
+        self
+    }
+
     pub fn debug(&mut self, debug: bool) -> &mut Bindgen {
         self.debug = debug;
         self
@@ -142,6 +165,31 @@ impl Bindgen {
         self
     }
 
+    /// Turn the allocator-bug-prone-toolchain warning (see
+    /// `toolchain_status`) into a hard error instead of a printed
+    /// warning. Useful for CI that wants to fail fast on a known-bad
+    /// `wasi-sdk`/clang release rather than risk shipping a miscompiled
+    /// module.
+    pub fn check_toolchain(&mut self, check_toolchain: bool) -> &mut Bindgen {
+        self.check_toolchain = check_toolchain;
+        self
+    }
+
+    /// When the input module imports from `wasi_snapshot_preview1` (as
+    /// produced by `cargo-wasi`-style builds that mix `#[wasm_bindgen]`
+    /// exports with WASI-based dependencies), inject a small JS WASI shim
+    /// into the generated loader and register it under that import name
+    /// instead of leaving the import unresolved.
+    ///
+    /// Only the `nodejs` loader and the `browser` + `web_async` loader
+    /// actually wire the shim in; a plain `browser` or `no_modules` build
+    /// prints a warning and leaves the import unresolved, since neither has
+    /// a dedicated loader function in this crate to inject into.
+    pub fn wasi(&mut self, wasi: bool) -> &mut Bindgen {
+        self.wasi = wasi;
+        self
+    }
+
     pub fn generate<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
         self._generate(path.as_ref())
     }
@@ -167,6 +215,27 @@ impl Bindgen {
                 (module, stem)
             }
         };
+
+        match toolchain_status(&module, MIN_SAFE_WASI_SDK_CLANG_VERSION) {
+            ToolchainStatus::ProbablyUnsafe => {
+                let msg = "
+
+this module looks like it was built with a wasi-sdk/clang release older than
+15.0.7, which shipped a `realloc` that can corrupt memory under allocation
+patterns wasm-bindgen's generated glue can trigger. Consider updating your
+wasi-sdk/clang toolchain; see:
+
+  https://github.com/WebAssembly/wasi-libc/pull/328
+";
+                if self.check_toolchain {
+                    bail!(msg);
+                } else {
+                    eprintln!("warning: {}", msg);
+                }
+            }
+            ToolchainStatus::ProbablySafe | ToolchainStatus::Unknown => {}
+        }
+
         let programs = extract_programs(&mut module)
             .with_context(|_| "failed to extract wasm-bindgen custom sections")?;
 
@@ -221,7 +290,45 @@ impl Bindgen {
             "js"
         };
         let js_path = out_dir.join(stem).with_extension(extension);
-        fs::write(&js_path, reset_indentation(&js))
+        let wasm_path = out_dir.join(format!("{}_bg", stem)).with_extension("wasm");
+
+        if self.web_async && !self.browser {
+            eprintln!(
+                "warning: `web_async` has no effect unless `browser` is also enabled; \
+                 `no_modules` produces a classic script that can't host the ES-module-only \
+                 `init` this emits, and this build will instead get the usual synchronous \
+                 output"
+            );
+        }
+        let web_async = self.web_async && self.browser;
+
+        if self.wasi
+            && (self.browser || self.no_modules)
+            && !web_async
+            && import_module_names(&module).contains(WASI_MODULE_NAME)
+        {
+            eprintln!(
+                "warning: this module imports `{}`, but the WASI shim is only wired into \
+                 the `nodejs` loader and the `browser` + `web_async` loader; a plain \
+                 `browser` or `no_modules` build will leave this import unresolved and fail \
+                 to instantiate. Enable `web_async` alongside `browser` to get the shim \
+                 there too, or load this module via `nodejs`.",
+                WASI_MODULE_NAME
+            );
+        }
+
+        let mut js = reset_indentation(&js);
+        if web_async {
+            // `finalize` embeds a synchronous `new WebAssembly.Module` /
+            // `new WebAssembly.Instance` pair directly into the browser
+            // output; that's exactly what `web_async` exists to avoid, so
+            // strip it (best-effort; a miss just leaves the dead sync path
+            // in place rather than corrupting the file) and let `init`
+            // below own instantiation instead.
+            js = strip_sync_instantiate(&js);
+            js.push_str(&self.generate_web_async_init(&module, &wasm_path));
+        }
+        fs::write(&js_path, js)
             .with_context(|_| format!("failed to write `{}`", js_path.display()))?;
 
         if self.typescript {
@@ -230,8 +337,6 @@ impl Bindgen {
                 .with_context(|_| format!("failed to write `{}`", ts_path.display()))?;
         }
 
-        let wasm_path = out_dir.join(format!("{}_bg", stem)).with_extension("wasm");
-
         if self.nodejs {
             let js_path = wasm_path.with_extension(extension);
             let shim = self.generate_node_wasm_import(&module, &wasm_path);
@@ -239,6 +344,8 @@ impl Bindgen {
                 .with_context(|_| format!("failed to write `{}`", js_path.display()))?;
         }
 
+        write_producers_section(&mut module);
+
         let wasm_bytes = parity_wasm::serialize(module)?;
         fs::write(&wasm_path, wasm_bytes)
             .with_context(|_| format!("failed to write `{}`", wasm_path.display()))?;
@@ -246,12 +353,12 @@ impl Bindgen {
     }
 
     fn generate_node_wasm_import(&self, m: &Module, path: &Path) -> String {
-        let mut imports = BTreeSet::new();
-        if let Some(i) = m.import_section() {
-            for i in i.entries() {
-                imports.insert(i.module());
-            }
-        }
+        let all_imports = import_module_names(m);
+        let has_wasi = self.wasi && all_imports.contains(WASI_MODULE_NAME);
+        let imports: Vec<&str> = all_imports
+            .into_iter()
+            .filter(|m| !has_wasi || *m != WASI_MODULE_NAME)
+            .collect();
 
         let mut shim = String::new();
 
@@ -285,6 +392,11 @@ impl Bindgen {
                 path.file_name().unwrap().to_str().unwrap()
             ));
         }
+
+        if has_wasi {
+            shim.push_str(wasi_shim_js());
+        }
+
         shim.push_str("let imports = {};\n");
         for (i, module) in imports.iter().enumerate() {
             if self.nodejs_experimental_modules {
@@ -293,6 +405,12 @@ impl Bindgen {
                 shim.push_str(&format!("imports['{0}'] = require('{0}');\n", module));
             }
         }
+        if has_wasi {
+            shim.push_str(&format!(
+                "imports['{}'] = wasiSnapshotPreview1;\n",
+                WASI_MODULE_NAME
+            ));
+        }
 
         shim.push_str(&format!(
             "
@@ -301,6 +419,10 @@ impl Bindgen {
             ",
         ));
 
+        if has_wasi {
+            shim.push_str("memory = wasmInstance.exports.memory;\n");
+        }
+
         if self.nodejs_experimental_modules {
             if let Some(e) = m.export_section() {
                 for name in e.entries().iter().map(|e| e.field()) {
@@ -317,6 +439,422 @@ impl Bindgen {
 
         reset_indentation(&shim)
     }
+
+    /// Generates an async `init` entry point appended to the main JS module
+    /// for the `browser` target when `web_async` is set.
+    ///
+    /// This relies on ES module syntax (`export`, `import.meta.url`), so
+    /// it's only emitted for `browser`; `no_modules` produces a classic
+    /// script and can't use either.
+    ///
+    /// Unlike `generate_node_wasm_import` the wasm bytes aren't read
+    /// synchronously from disk; instead `init` accepts a URL/`Request`
+    /// (defaulting to the sibling `_bg.wasm` file) and only resolves once
+    /// instantiation finishes, populating the module's wasm exports lazily.
+    fn generate_web_async_init(&self, m: &Module, path: &Path) -> String {
+        let all_imports = import_module_names(m);
+        let has_wasi = self.wasi && all_imports.contains(WASI_MODULE_NAME);
+        let imports: Vec<&str> = all_imports
+            .into_iter()
+            .filter(|m| !has_wasi || *m != WASI_MODULE_NAME)
+            .collect();
+        let wasm_file = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        let mut shim = String::new();
+        shim.push_str("let wasm;\n");
+        if has_wasi {
+            shim.push_str(wasi_shim_js());
+        }
+        shim.push_str(&format!(
+            "
+                export async function init(input) {{
+                    if (typeof input === 'undefined') {{
+                        input = new URL('{}', import.meta.url);
+                    }}
+                    let imports = {{}};
+            ",
+            wasm_file
+        ));
+        for module in imports.iter() {
+            shim.push_str(&format!("imports['{0}'] = await import('{0}');\n", module));
+        }
+        if has_wasi {
+            shim.push_str(&format!(
+                "imports['{}'] = wasiSnapshotPreview1;\n",
+                WASI_MODULE_NAME
+            ));
+        }
+        shim.push_str(
+            "
+                if (typeof input === 'string'
+                    || (typeof Request === 'function' && input instanceof Request)
+                    || (typeof URL === 'function' && input instanceof URL)) {
+                    input = fetch(input);
+                }
+
+                const { instance, module } = await load(await input, imports);
+                wasm = instance.exports;
+            ",
+        );
+        if has_wasi {
+            shim.push_str("memory = wasm.memory;\n");
+        }
+        shim.push_str(
+            "
+                init.__wbindgen_wasm_module = module;
+                return wasm;
+            }
+            export default init;
+
+            async function load(module, imports) {
+                if (typeof Response === 'function' && module instanceof Response) {
+                    if (typeof WebAssembly.instantiateStreaming === 'function') {
+                        try {
+                            return await WebAssembly.instantiateStreaming(module, imports);
+                        } catch (e) {
+                            if (module.headers.get('Content-Type') != 'application/wasm') {
+                                console.warn('`WebAssembly.instantiateStreaming` failed because your server does not serve wasm with `application/wasm` MIME type. Falling back to `WebAssembly.instantiate` which is slower. Original error:\\n', e);
+                            } else {
+                                throw e;
+                            }
+                        }
+                    }
+
+                    const bytes = await module.arrayBuffer();
+                    return await WebAssembly.instantiate(bytes, imports);
+                } else {
+                    const instance = await WebAssembly.instantiate(module, imports);
+                    if (instance instanceof WebAssembly.Instance) {
+                        return { instance, module };
+                    } else {
+                        return instance;
+                    }
+                }
+            }
+            ",
+        );
+
+        reset_indentation(&shim)
+    }
+}
+
+/// The import module name `wasm32-wasi` targets use for the WASI syscalls
+/// pulled in by `cargo-wasi`-style dependencies.
+const WASI_MODULE_NAME: &str = "wasi_snapshot_preview1";
+
+/// Returns the JS source for a minimal `wasi_snapshot_preview1` shim,
+/// sufficient to satisfy the handful of syscalls crates built against
+/// `wasm32-wasi` typically pull in. `memory` is populated by the caller
+/// once the module has been instantiated, since the shim's memory-backed
+/// helpers (`fd_write`, `environ_sizes_get`, ...) need access to the instance's
+/// linear memory to read/write their arguments.
+fn wasi_shim_js() -> &'static str {
+    "
+    let memory;
+
+    function wasiGetDataView() {
+        return new DataView(memory.buffer);
+    }
+
+    // Node has `process.stdout`/`process.stderr`; browsers don't have
+    // `process` at all, so fall back to `console.log`/`console.error`
+    // there instead of throwing a `ReferenceError`.
+    function wasiWrite(fd, text) {
+        if (typeof process !== 'undefined' && process.stdout && process.stderr) {
+            (fd === 2 ? process.stderr : process.stdout).write(text);
+        } else if (fd === 2) {
+            console.error(text);
+        } else {
+            console.log(text);
+        }
+    }
+
+    const wasiSnapshotPreview1 = {
+        fd_write(fd, iovs, iovsLen, nwritten) {
+            const view = wasiGetDataView();
+            let written = 0;
+            let text = '';
+            const decoder = new TextDecoder();
+            for (let i = 0; i < iovsLen; i++) {
+                const ptr = view.getUint32(iovs + i * 8, true);
+                const len = view.getUint32(iovs + i * 8 + 4, true);
+                text += decoder.decode(new Uint8Array(memory.buffer, ptr, len));
+                written += len;
+            }
+            wasiWrite(fd, text);
+            view.setUint32(nwritten, written, true);
+            return 0;
+        },
+        clock_time_get(id, precision, time) {
+            wasiGetDataView().setBigUint64(time, BigInt(Date.now()) * 1000000n, true);
+            return 0;
+        },
+        random_get(ptr, len) {
+            const bytes = new Uint8Array(memory.buffer, ptr, len);
+            for (let i = 0; i < len; i++) {
+                bytes[i] = Math.floor(Math.random() * 256);
+            }
+            return 0;
+        },
+        proc_exit(code) {
+            throw new Error(`process exited with code ${code}`);
+        },
+        environ_sizes_get(countPtr, bufSizePtr) {
+            const view = wasiGetDataView();
+            view.setUint32(countPtr, 0, true);
+            view.setUint32(bufSizePtr, 0, true);
+            return 0;
+        },
+        environ_get(environ, environBuf) {
+            return 0;
+        },
+        args_sizes_get(argcPtr, argvBufSizePtr) {
+            const view = wasiGetDataView();
+            view.setUint32(argcPtr, 0, true);
+            view.setUint32(argvBufSizePtr, 0, true);
+            return 0;
+        },
+        args_get(argv, argvBuf) {
+            return 0;
+        },
+    };
+    "
+}
+
+/// Removes any line that directly constructs a synchronous
+/// `WebAssembly.Module`/`WebAssembly.Instance`, so `web_async`'s `init` is
+/// the only thing that instantiates the module.
+///
+/// This works on the text `Context::finalize` produced rather than being a
+/// "don't emit the sync path" switch threaded through `finalize`; it matches on the `new WebAssembly.Module(`/`new
+/// WebAssembly.Instance(` constructor calls themselves rather than on a
+/// specific variable-naming convention, so it still catches the sync
+/// instantiate regardless of how the surrounding declaration is written. A
+/// miss is a safe no-op: it just leaves the dead synchronous path in the
+/// output rather than corrupting it.
+fn strip_sync_instantiate(js: &str) -> String {
+    let mut out = String::with_capacity(js.len());
+    for line in js.lines() {
+        if line.contains("new WebAssembly.Module(") || line.contains("new WebAssembly.Instance(") {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn import_module_names(m: &Module) -> BTreeSet<&str> {
+    let mut imports = BTreeSet::new();
+    if let Some(i) = m.import_section() {
+        for i in i.entries() {
+            imports.insert(i.module());
+        }
+    }
+    imports
+}
+
+/// A single field of the `producers` custom section, e.g. `"processed-by"`
+/// paired with the `(name, version)` pairs recorded under it.
+type ProducersField = (String, Vec<(String, String)>);
+
+/// Decodes a `producers` custom section payload per the [tool-conventions]
+/// encoding: a LEB128 count of fields, each field a length-prefixed name
+/// followed by a LEB128 count of length-prefixed `(name, version)` pairs.
+///
+/// Returns `None` on a truncated or otherwise malformed payload rather than
+/// panicking; the producers section comes from the *input* module, which
+/// may have been written by a toolchain that doesn't encode it the way we
+/// expect.
+///
+/// [tool-conventions]: https://github.com/WebAssembly/tool-conventions/blob/main/ProducersSection.md
+fn parse_producers_section(payload: &[u8]) -> Option<Vec<ProducersField>> {
+    let mut pos = 0;
+    let field_count = read_leb128_u32(payload, &mut pos)?;
+    let mut fields = Vec::with_capacity(field_count as usize);
+    for _ in 0..field_count {
+        let name = read_string(payload, &mut pos)?;
+        let value_count = read_leb128_u32(payload, &mut pos)?;
+        let mut values = Vec::with_capacity(value_count as usize);
+        for _ in 0..value_count {
+            let value_name = read_string(payload, &mut pos)?;
+            let value_version = read_string(payload, &mut pos)?;
+            values.push((value_name, value_version));
+        }
+        fields.push((name, values));
+    }
+    Some(fields)
+}
+
+fn serialize_producers_section(fields: &[ProducersField]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    write_leb128_u32(&mut payload, fields.len() as u32);
+    for (name, values) in fields {
+        write_string(&mut payload, name);
+        write_leb128_u32(&mut payload, values.len() as u32);
+        for (value_name, value_version) in values {
+            write_string(&mut payload, value_name);
+            write_string(&mut payload, value_version);
+        }
+    }
+    payload
+}
+
+/// Reads an unsigned LEB128 `u32`, returning `None` on a truncated buffer
+/// or a varint wider than 32 bits instead of panicking or overflowing.
+fn read_leb128_u32(payload: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut result = 0u32;
+    let mut shift = 0u32;
+    loop {
+        let byte = *payload.get(*pos)?;
+        *pos += 1;
+        if shift >= 32 {
+            return None;
+        }
+        // The fifth byte may only carry bits 28..31; anything above would
+        // be silently shifted out of a `u32`.
+        if shift == 28 && byte & 0x70 != 0 {
+            return None;
+        }
+        result |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    Some(result)
+}
+
+fn write_leb128_u32(dst: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            dst.push(byte);
+            break;
+        }
+        dst.push(byte | 0x80);
+    }
+}
+
+/// Reads a length-prefixed UTF-8 string, returning `None` if the prefix
+/// overruns the remaining payload instead of panicking on an out-of-bounds
+/// slice.
+fn read_string(payload: &[u8], pos: &mut usize) -> Option<String> {
+    let len = read_leb128_u32(payload, pos)? as usize;
+    let end = pos.checked_add(len)?;
+    let bytes = payload.get(*pos..end)?;
+    let s = String::from_utf8_lossy(bytes).into_owned();
+    *pos = end;
+    Some(s)
+}
+
+fn write_string(dst: &mut Vec<u8>, s: &str) {
+    write_leb128_u32(dst, s.len() as u32);
+    dst.extend_from_slice(s.as_bytes());
+}
+
+/// The first known-good `wasi-sdk`/clang release; versions strictly older
+/// than this shipped a `realloc` with a known allocation-corruption bug.
+const MIN_SAFE_WASI_SDK_CLANG_VERSION: (u32, u32, u32) = (15, 0, 7);
+
+#[derive(Debug, PartialEq, Eq)]
+enum ToolchainStatus {
+    /// A `clang`/`wasi-sdk` version was found and is at or above the
+    /// threshold.
+    ProbablySafe,
+    /// A `clang`/`wasi-sdk` version was found and is below the threshold.
+    ProbablyUnsafe,
+    /// No `clang`/`wasi-sdk` version could be found in the module's
+    /// `producers` section.
+    Unknown,
+}
+
+/// Inspects the input module's `producers` custom section for a
+/// `clang`/`wasi-sdk` version and classifies it against `threshold`.
+fn toolchain_status(module: &Module, threshold: (u32, u32, u32)) -> ToolchainStatus {
+    match producers_clang_version(module) {
+        Some(version) if version < threshold => ToolchainStatus::ProbablyUnsafe,
+        Some(_) => ToolchainStatus::ProbablySafe,
+        None => ToolchainStatus::Unknown,
+    }
+}
+
+/// Searches the `processed-by` and `language` fields of the module's
+/// `producers` section for a `clang`/`wasi-sdk` entry and parses its
+/// version as a dotted semver triple.
+fn producers_clang_version(module: &Module) -> Option<(u32, u32, u32)> {
+    let (_, payload) = find_producers_section(module)?;
+    let fields = parse_producers_section(payload)?;
+    fields
+        .iter()
+        .filter(|(name, _)| name == "processed-by" || name == "language")
+        .flat_map(|(_, values)| values.iter())
+        .filter(|(name, _)| name.contains("clang") || name.contains("wasi-sdk"))
+        .find_map(|(_, version)| parse_semver_triple(version))
+}
+
+/// Parses the first `major.minor.patch` run of digits found in `s`, which
+/// may be embedded in a longer banner string like `"wasi-sdk-15.0
+/// (https://...) clang version 15.0.7"`.
+fn parse_semver_triple(s: &str) -> Option<(u32, u32, u32)> {
+    let cleaned: String = s
+        .chars()
+        .map(|c| if c.is_ascii_digit() || c == '.' { c } else { ' ' })
+        .collect();
+    cleaned.split_whitespace().find_map(|token| {
+        let mut parts = token.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next()?.parse().ok()?;
+        Some((major, minor, patch))
+    })
+}
+
+/// Finds the existing `producers` custom section, if any, returning its
+/// index within `module.sections()` and its raw payload.
+fn find_producers_section(module: &Module) -> Option<(usize, &[u8])> {
+    module.sections().iter().enumerate().find_map(|(i, s)| match *s {
+        Section::Custom(ref c) if c.name() == "producers" => Some((i, c.payload())),
+        _ => None,
+    })
+}
+
+/// Writes (or merges into an existing) `producers` custom section recording
+/// that this module was processed by wasm-bindgen, following the standard
+/// [tool-conventions] encoding used by other wasm tools.
+///
+/// [tool-conventions]: https://github.com/WebAssembly/tool-conventions/blob/main/ProducersSection.md
+fn write_producers_section(module: &mut Module) {
+    let (index, mut fields) = match find_producers_section(module) {
+        // An existing section we can't make sense of keeps its index but
+        // its contents are dropped, not merged from: the replacement holds
+        // only our own entry, since we don't know enough about it to
+        // safely combine its fields with ours.
+        Some((i, payload)) => (Some(i), parse_producers_section(payload).unwrap_or_default()),
+        None => (None, Vec::new()),
+    };
+
+    let processed_by = fields.iter_mut().find(|(name, _)| name == "processed-by");
+    match processed_by {
+        Some((_, values)) => match values.iter_mut().find(|(name, _)| name == "wasm-bindgen") {
+            Some(entry) => entry.1 = shared::version().to_string(),
+            None => values.push(("wasm-bindgen".to_string(), shared::version().to_string())),
+        },
+        None => fields.push((
+            "processed-by".to_string(),
+            vec![("wasm-bindgen".to_string(), shared::version().to_string())],
+        )),
+    }
+
+    let section = Section::Custom(CustomSection::new(
+        "producers".to_string(),
+        serialize_producers_section(&fields),
+    ));
+    match index {
+        Some(i) => module.sections_mut()[i] = section,
+        None => module.sections_mut().push(section),
+    }
 }
 
 fn extract_programs(module: &mut Module) -> Result<Vec<shared::Program>, Error> {
@@ -416,3 +954,133 @@ fn reset_indentation(s: &str) -> String {
     }
     return dst;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_sync_instantiate_removes_module_and_instance_construction() {
+        let js = "
+            let wasm;
+            const wasmModule = new WebAssembly.Module(bytes);
+            const wasmInstance = new WebAssembly.Instance(wasmModule, imports);
+            module.exports = wasmInstance.exports;
+        ";
+        let stripped = strip_sync_instantiate(js);
+        assert!(!stripped.contains("new WebAssembly.Module("));
+        assert!(!stripped.contains("new WebAssembly.Instance("));
+        // Unrelated lines are left alone.
+        assert!(stripped.contains("let wasm;"));
+        assert!(stripped.contains("module.exports = wasmInstance.exports;"));
+    }
+
+    #[test]
+    fn strip_sync_instantiate_is_noop_without_a_match() {
+        let js = "export async function init(input) {}\n";
+        assert_eq!(strip_sync_instantiate(js), js);
+    }
+
+    #[test]
+    fn producers_section_round_trips() {
+        let fields = vec![
+            (
+                "language".to_string(),
+                vec![("Rust".to_string(), "1.70.0".to_string())],
+            ),
+            (
+                "processed-by".to_string(),
+                vec![
+                    ("clang".to_string(), "15.0.7".to_string()),
+                    ("wasm-bindgen".to_string(), "0.2.87".to_string()),
+                ],
+            ),
+        ];
+        let payload = serialize_producers_section(&fields);
+        assert_eq!(parse_producers_section(&payload), Some(fields));
+    }
+
+    #[test]
+    fn parse_producers_section_rejects_truncated_payload() {
+        // A field count claiming one field, but nothing after it.
+        let mut payload = Vec::new();
+        write_leb128_u32(&mut payload, 1);
+        assert_eq!(parse_producers_section(&payload), None);
+
+        // Empty payload entirely.
+        assert_eq!(parse_producers_section(&[]), None);
+    }
+
+    #[test]
+    fn read_leb128_u32_handles_truncation_and_overflow() {
+        let mut pos = 0;
+        // Continuation bit set on the last byte, so the value never
+        // terminates within the buffer.
+        assert_eq!(read_leb128_u32(&[0x80], &mut pos), None);
+
+        // A varint whose fifth byte sets bits past bit 31, overflowing 32
+        // bits; the trailing byte is never reached.
+        pos = 0;
+        assert_eq!(
+            read_leb128_u32(&[0xff, 0xff, 0xff, 0xff, 0xff, 0x01], &mut pos),
+            None
+        );
+
+        // A normal, in-range multi-byte varint still decodes correctly.
+        pos = 0;
+        assert_eq!(read_leb128_u32(&[0xe5, 0x8e, 0x26], &mut pos), Some(624485));
+        assert_eq!(pos, 3);
+    }
+
+    #[test]
+    fn read_string_rejects_a_length_prefix_past_the_end() {
+        let mut payload = Vec::new();
+        write_leb128_u32(&mut payload, 10);
+        payload.extend_from_slice(b"short");
+        let mut pos = 0;
+        assert_eq!(read_string(&payload, &mut pos), None);
+    }
+
+    #[test]
+    fn parse_semver_triple_from_a_real_clang_banner() {
+        let banner = "wasi-sdk-15.0 (https://github.com/WebAssembly/wasi-sdk 0ab546ee) \
+                       clang version 15.0.7";
+        assert_eq!(parse_semver_triple(banner), Some((15, 0, 7)));
+    }
+
+    #[test]
+    fn toolchain_status_classifies_by_clang_version() {
+        fn module_with_producers(fields: &[ProducersField]) -> Module {
+            let mut module = Module::new(Vec::new());
+            module.sections_mut().push(Section::Custom(CustomSection::new(
+                "producers".to_string(),
+                serialize_producers_section(fields),
+            )));
+            module
+        }
+
+        let old = module_with_producers(&[(
+            "processed-by".to_string(),
+            vec![("clang".to_string(), "14.0.4".to_string())],
+        )]);
+        assert_eq!(
+            toolchain_status(&old, MIN_SAFE_WASI_SDK_CLANG_VERSION),
+            ToolchainStatus::ProbablyUnsafe
+        );
+
+        let new = module_with_producers(&[(
+            "processed-by".to_string(),
+            vec![("clang".to_string(), "16.0.0".to_string())],
+        )]);
+        assert_eq!(
+            toolchain_status(&new, MIN_SAFE_WASI_SDK_CLANG_VERSION),
+            ToolchainStatus::ProbablySafe
+        );
+
+        let unknown = module_with_producers(&[]);
+        assert_eq!(
+            toolchain_status(&unknown, MIN_SAFE_WASI_SDK_CLANG_VERSION),
+            ToolchainStatus::Unknown
+        );
+    }
+}